@@ -0,0 +1,168 @@
+//! Time sources.
+//!
+//! Anything time-dependent (caches, cookie expiry, rate limiters, timeouts)
+//! should ask a [`Clock`] for the time instead of calling [`Instant::now`] or
+//! [`SystemTime::now`] directly, so it can be driven by a [`TestClock`] in
+//! unit tests rather than by real sleeps.
+
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+    /// The current monotonic time, for measuring elapsed durations.
+    fn now(&self) -> Instant;
+
+    /// The current wall-clock time, for comparing against absolute dates.
+    fn system_now(&self) -> SystemTime;
+
+    /// Block the current thread for the given duration.
+    fn sleep(&self, duration: Duration);
+}
+
+/// The real clock, backed by the operating system.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// A manually driven clock for tests.
+///
+/// Time only moves when [`TestClock::advance`] or [`Clock::sleep`] is
+/// called. Clones share the same time, so a test can keep one handle and
+/// give another to the code under test.
+///
+/// ```
+/// use std::time::Duration;
+/// use habanero::clock::{Clock, TestClock};
+///
+/// let clock = TestClock::new();
+/// let start = clock.now();
+/// clock.advance(Duration::from_secs(30));
+/// assert_eq!(clock.now() - start, Duration::from_secs(30));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TestClock {
+    instant: Instant,
+    system: SystemTime,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl TestClock {
+    /// Create a test clock starting at the current time.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::at(SystemTime::now())
+    }
+
+    /// Create a test clock whose wall-clock time starts at `system`.
+    #[must_use]
+    pub fn at(system: SystemTime) -> Self {
+        Self {
+            instant: Instant::now(),
+            system,
+            elapsed: Arc::new(Mutex::new(Duration::ZERO)),
+        }
+    }
+
+    /// Move the clock forward by the given duration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the resulting time cannot be represented as an [`Instant`]
+    /// or [`SystemTime`].
+    pub fn advance(&self, duration: Duration) {
+        let mut elapsed = self.lock();
+        let advanced = elapsed
+            .checked_add(duration)
+            .filter(|advanced| {
+                self.instant.checked_add(*advanced).is_some()
+                    && self.system.checked_add(*advanced).is_some()
+            })
+            .expect("test clock advanced beyond the representable time range");
+        *elapsed = advanced;
+    }
+
+    /// The total time the clock has been advanced by.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        *self.lock()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Duration> {
+        self.elapsed.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for TestClock {
+    fn now(&self) -> Instant {
+        self.instant + self.elapsed()
+    }
+
+    fn system_now(&self) -> SystemTime {
+        self.system + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_only_moves_when_advanced() {
+        let clock = TestClock::new();
+        let now = clock.now();
+        assert_eq!(clock.now(), now);
+
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(clock.now() - now, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_clock_sleep_advances_without_blocking() {
+        let clock = TestClock::new();
+        clock.sleep(Duration::from_hours(1));
+        assert_eq!(clock.elapsed(), Duration::from_hours(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "test clock advanced beyond the representable time range")]
+    fn test_clock_rejects_unrepresentable_advance() {
+        let clock = TestClock::new();
+        clock.advance(Duration::from_hours(1));
+        clock.advance(Duration::MAX);
+    }
+
+    #[test]
+    fn test_clock_clones_share_time() {
+        let epoch = SystemTime::UNIX_EPOCH;
+        let clock = TestClock::at(epoch);
+        let shared = clock.clone();
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(shared.system_now(), epoch + Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,708 @@
+//! Header names and values, raw and typed.
+
+use std::fmt::{self, Display, Formatter, Write};
+use std::hash::{Hash, Hasher};
+use std::str::{self, FromStr, Utf8Error};
+
+/// A header name, validated to contain only token characters.
+///
+/// Names keep the case they were created with but compare and hash
+/// case-insensitively, as header names do on the wire.
+///
+/// ```
+/// use habanero::header::HeaderName;
+///
+/// let name: HeaderName = "Content-Type".parse().unwrap();
+/// assert_eq!(name, "content-type".parse().unwrap());
+/// assert_eq!(name.as_str(), "Content-Type");
+///
+/// assert!("X-Evil\r\nSet-Cookie".parse::<HeaderName>().is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct HeaderName {
+    name: String,
+}
+
+impl HeaderName {
+    /// Get the name as a string slice.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+}
+
+impl AsRef<str> for HeaderName {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Display for HeaderName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl FromStr for HeaderName {
+    type Err = InvalidHeaderName;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.bytes().all(is_token_char) {
+            return Err(InvalidHeaderName);
+        }
+        Ok(Self {
+            name: s.to_string(),
+        })
+    }
+}
+
+impl PartialEq for HeaderName {
+    fn eq(&self, other: &Self) -> bool {
+        self.name.eq_ignore_ascii_case(&other.name)
+    }
+}
+
+impl Eq for HeaderName {}
+
+impl Hash for HeaderName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for byte in self.name.bytes() {
+            state.write_u8(byte.to_ascii_lowercase());
+        }
+        state.write_u8(0xFF);
+    }
+}
+
+/// An error creating a [`HeaderName`] that is empty or contains characters
+/// other than token characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidHeaderName;
+
+impl Display for InvalidHeaderName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("header name is empty or contains non-token characters")
+    }
+}
+
+impl std::error::Error for InvalidHeaderName {}
+
+/// A header value, stored as the raw bytes seen on the wire.
+///
+/// Servers may send Latin-1 or opaque bytes in header values, which a
+/// `String` cannot hold without failing or corrupting them. A
+/// `HeaderValue` keeps them exactly; [`HeaderValue::to_str`] succeeds only
+/// if they happen to be UTF-8.
+///
+/// Values are validated on construction: CR, LF and other control bytes
+/// (except horizontal tab) are rejected, so a value can never inject extra
+/// header lines.
+///
+/// ```
+/// use habanero::header::HeaderValue;
+///
+/// let value = HeaderValue::from_bytes(b"caf\xe9").unwrap();
+/// assert_eq!(value.as_bytes(), b"caf\xe9");
+/// assert!(value.to_str().is_err());
+///
+/// assert!(HeaderValue::from_bytes("a\r\nSet-Cookie: b").is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HeaderValue {
+    bytes: Vec<u8>,
+}
+
+impl HeaderValue {
+    /// Create a header value from raw bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes contain control characters other than
+    /// horizontal tab.
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Result<Self, InvalidHeaderValue> {
+        let bytes = bytes.into();
+        if bytes
+            .iter()
+            .any(|&byte| byte != b'\t' && byte.is_ascii_control())
+        {
+            return Err(InvalidHeaderValue);
+        }
+        Ok(Self { bytes })
+    }
+
+    /// Get the value as a string slice.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not valid UTF-8.
+    pub fn to_str(&self) -> Result<&str, Utf8Error> {
+        str::from_utf8(&self.bytes)
+    }
+
+    /// Get the raw bytes of the value.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Consume the value, returning its raw bytes.
+    #[must_use]
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl AsRef<[u8]> for HeaderValue {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl FromStr for HeaderValue {
+    type Err = InvalidHeaderValue;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_bytes(s)
+    }
+}
+
+/// An error creating a [`HeaderValue`] from bytes containing control
+/// characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidHeaderValue;
+
+impl Display for InvalidHeaderValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("header value contains control characters")
+    }
+}
+
+impl std::error::Error for InvalidHeaderValue {}
+
+/// The disposition type of a [`ContentDisposition`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DispositionType {
+    /// Display the content in place.
+    Inline,
+    /// Download the content, usually as a file.
+    Attachment,
+    /// A field of a `multipart/form-data` body.
+    FormData,
+}
+
+impl DispositionType {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Inline => "inline",
+            Self::Attachment => "attachment",
+            Self::FormData => "form-data",
+        }
+    }
+}
+
+/// A `Content-Disposition` header value, as described in RFC 6266 and
+/// RFC 7578.
+///
+/// Filenames containing characters outside printable ASCII are written
+/// twice: as an ASCII approximation in `filename` for old clients, and
+/// exactly in an RFC 5987 encoded `filename*` parameter which modern
+/// browsers prefer. Multipart field names are written as raw UTF-8, as
+/// RFC 7578 section 5.1 allows. Control characters are stripped from both,
+/// whether set here or parsed.
+///
+/// Filenames are also reduced to their last path segment, with bidi
+/// formatting characters removed, as RFC 6266 section 4.3 recommends, so a
+/// parsed filename never points outside the download directory or hides
+/// its extension. A filename with nothing left, such as `..`, is dropped.
+///
+/// ```
+/// use habanero::header::ContentDisposition;
+///
+/// let disposition = ContentDisposition::attachment().filename("résumé.pdf");
+/// assert_eq!(
+///     disposition.to_string(),
+///     "attachment; filename=\"r_sum_.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf",
+/// );
+///
+/// let parsed: ContentDisposition = disposition.to_string().parse().unwrap();
+/// assert_eq!(parsed.get_filename(), Some("résumé.pdf"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentDisposition {
+    kind: DispositionType,
+    name: Option<String>,
+    filename: Option<String>,
+}
+
+impl ContentDisposition {
+    /// Create an `inline` disposition.
+    #[must_use]
+    pub fn inline() -> Self {
+        Self::new(DispositionType::Inline)
+    }
+
+    /// Create an `attachment` disposition.
+    #[must_use]
+    pub fn attachment() -> Self {
+        Self::new(DispositionType::Attachment)
+    }
+
+    /// Create a `form-data` disposition for the multipart field `name`.
+    #[must_use]
+    pub fn form_data(name: &str) -> Self {
+        Self {
+            name: Some(strip_control(name)),
+            ..Self::new(DispositionType::FormData)
+        }
+    }
+
+    fn new(kind: DispositionType) -> Self {
+        Self {
+            kind,
+            name: None,
+            filename: None,
+        }
+    }
+
+    /// Set the filename the content should be saved as.
+    #[must_use]
+    pub fn filename(mut self, filename: &str) -> Self {
+        self.filename = sanitize_filename(filename);
+        self
+    }
+
+    /// Get the disposition type.
+    #[must_use]
+    pub fn get_kind(&self) -> DispositionType {
+        self.kind
+    }
+
+    /// Get the multipart field name, if any.
+    #[must_use]
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Get the filename, if any.
+    #[must_use]
+    pub fn get_filename(&self) -> Option<&str> {
+        self.filename.as_deref()
+    }
+}
+
+impl Display for ContentDisposition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.kind.as_str())?;
+        if let Some(name) = &self.name {
+            write!(f, "; name=")?;
+            write_quoted(f, name, false)?;
+        }
+        if let Some(filename) = &self.filename {
+            write!(f, "; filename=")?;
+            write_quoted(f, filename, true)?;
+            if !filename.chars().all(is_quotable) {
+                write!(f, "; filename*=UTF-8''")?;
+                for byte in filename.bytes() {
+                    if is_attr_char(byte) {
+                        f.write_char(char::from(byte))?;
+                    } else {
+                        write!(f, "%{byte:02X}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ContentDisposition {
+    type Err = ContentDispositionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, mut rest) = s.split_once(';').unwrap_or((s, ""));
+        let kind = kind.trim();
+        if kind.is_empty() || !kind.bytes().all(is_token_char) {
+            return Err(ContentDispositionError::InvalidType);
+        }
+
+        // RFC 6266 section 4.2: unknown types are handled as attachments.
+        let mut disposition = Self::new(match kind.to_ascii_lowercase().as_str() {
+            "inline" => DispositionType::Inline,
+            "form-data" => DispositionType::FormData,
+            _ => DispositionType::Attachment,
+        });
+        let mut extended = None;
+        let mut undecodable = false;
+        let mut seen = Vec::new();
+
+        while !rest.trim().is_empty() {
+            let (key, after) = rest
+                .split_once('=')
+                .ok_or(ContentDispositionError::MalformedParameter)?;
+            let key = key.trim().to_ascii_lowercase();
+            let name = key.strip_suffix('*').unwrap_or(&key);
+            // RFC 6266 section 4.1: parameter names must not repeat.
+            if name.is_empty() || !name.bytes().all(is_token_char) || seen.contains(&key) {
+                return Err(ContentDispositionError::MalformedParameter);
+            }
+            let (value, after) = parse_value(after.trim_start())?;
+            rest = match after.trim_start() {
+                "" => "",
+                after => after
+                    .strip_prefix(';')
+                    .ok_or(ContentDispositionError::MalformedParameter)?,
+            };
+
+            match key.as_str() {
+                "name" => disposition.name = Some(strip_control(&value)),
+                "filename" => disposition.filename = sanitize_filename(&value),
+                // RFC 6266 section 4.3: fall back to `filename` when the
+                // extended form can't be used.
+                "filename*" => match decode_extended(&value) {
+                    Some(filename) => extended = sanitize_filename(&filename),
+                    None => undecodable = true,
+                },
+                _ => {}
+            }
+            seen.push(key);
+        }
+
+        if extended.is_some() {
+            disposition.filename = extended;
+        } else if undecodable && disposition.filename.is_none() {
+            return Err(ContentDispositionError::InvalidEncoding);
+        }
+        Ok(disposition)
+    }
+}
+
+/// An error parsing a [`ContentDisposition`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContentDispositionError {
+    /// The disposition type is missing or not a token.
+    InvalidType,
+    /// A parameter is not a well-formed `key=value` pair.
+    MalformedParameter,
+    /// An extended filename has an unsupported charset or bad encoding, and
+    /// there is no plain filename to fall back to.
+    InvalidEncoding,
+}
+
+impl Display for ContentDispositionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::InvalidType => "invalid disposition type",
+            Self::MalformedParameter => "malformed disposition parameter",
+            Self::InvalidEncoding => "invalid extended parameter encoding",
+        })
+    }
+}
+
+impl std::error::Error for ContentDispositionError {}
+
+/// Write a quoted-string, replacing non-ASCII characters with `_` if
+/// `ascii_only` is set.
+fn write_quoted(f: &mut Formatter<'_>, value: &str, ascii_only: bool) -> fmt::Result {
+    f.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => write!(f, "\\{c}")?,
+            c if ascii_only && !is_quotable(c) => f.write_char('_')?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Parse a token or quoted-string, returning it and the remaining input.
+fn parse_value(s: &str) -> Result<(String, &str), ContentDispositionError> {
+    let Some(quoted) = s.strip_prefix('"') else {
+        let end = s.find(|c: char| c == ';' || c.is_ascii_whitespace());
+        let (value, rest) = s.split_at(end.unwrap_or(s.len()));
+        if value.is_empty() {
+            return Err(ContentDispositionError::MalformedParameter);
+        }
+        return Ok((value.to_string(), rest));
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &quoted[i + 1..])),
+            '\\' => value.push(
+                chars
+                    .next()
+                    .ok_or(ContentDispositionError::MalformedParameter)?
+                    .1,
+            ),
+            c => value.push(c),
+        }
+    }
+    Err(ContentDispositionError::MalformedParameter)
+}
+
+/// Decode an RFC 5987 `charset'language'value` extended value, returning
+/// `None` for unsupported charsets or bad encoding.
+fn decode_extended(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let (Some(charset), Some(_language), Some(encoded)) =
+        (parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut iter = encoded.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let (high, low) = (iter.next()?, iter.next()?);
+            if !high.is_ascii_hexdigit() || !low.is_ascii_hexdigit() {
+                return None;
+            }
+            let hex = [high, low];
+            bytes.push(u8::from_str_radix(str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    if charset.eq_ignore_ascii_case("utf-8") {
+        String::from_utf8(bytes).ok()
+    } else if charset.eq_ignore_ascii_case("iso-8859-1") {
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
+    }
+}
+
+fn strip_control(value: &str) -> String {
+    value.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Reduce a filename to its last path segment without control or bidi
+/// formatting characters, or `None` if nothing usable is left.
+fn sanitize_filename(value: &str) -> Option<String> {
+    let segment = value.rsplit(['/', '\\']).next().unwrap_or(value);
+    let filename: String = segment
+        .chars()
+        .filter(|&c| !c.is_control() && !is_bidi_format(c))
+        .collect();
+    match filename.as_str() {
+        "" | "." | ".." => None,
+        _ => Some(filename),
+    }
+}
+
+/// Characters that change text direction, which can disguise a filename's
+/// extension.
+fn is_bidi_format(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061c}' | '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+fn is_quotable(c: char) -> bool {
+    c == ' ' || c.is_ascii_graphic()
+}
+
+fn is_attr_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte)
+}
+
+fn is_token_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_name_accepts_tokens() {
+        let name: HeaderName = "X-Request-Id".parse().unwrap();
+        assert_eq!(name.as_str(), "X-Request-Id");
+        assert_eq!(name.to_string(), "X-Request-Id");
+        assert!("!#$%&'*+-.^_`|~09azAZ".parse::<HeaderName>().is_ok());
+    }
+
+    #[test]
+    fn header_name_rejects_non_tokens() {
+        for name in ["", "Content Type", "Host:", "a\r\nb", "a\"b", "名前", "(x)"] {
+            assert_eq!(name.parse::<HeaderName>(), Err(InvalidHeaderName), "{name}");
+        }
+    }
+
+    #[test]
+    fn header_name_is_case_insensitive() {
+        use std::collections::HashSet;
+
+        let names: HashSet<HeaderName> = ["Accept", "ACCEPT", "accept"]
+            .into_iter()
+            .map(|name| name.parse().unwrap())
+            .collect();
+        assert_eq!(names.len(), 1);
+    }
+
+    #[test]
+    fn header_value_keeps_opaque_bytes() {
+        let value = HeaderValue::from_bytes(vec![b'a', 0xA3, b'\t', 0xFF]).unwrap();
+        assert_eq!(value.as_bytes(), [b'a', 0xA3, b'\t', 0xFF]);
+        assert!(value.to_str().is_err());
+        assert_eq!(value.into_bytes(), [b'a', 0xA3, b'\t', 0xFF]);
+    }
+
+    #[test]
+    fn header_value_to_str() {
+        let value: HeaderValue = "text/html; charset=utf-8".parse().unwrap();
+        assert_eq!(value.to_str(), Ok("text/html; charset=utf-8"));
+    }
+
+    #[test]
+    fn header_value_rejects_control_characters() {
+        for bytes in [&b"a\rb"[..], b"a\nb", b"a\0b", b"a\x7fb"] {
+            assert_eq!(HeaderValue::from_bytes(bytes), Err(InvalidHeaderValue));
+        }
+    }
+
+    #[test]
+    fn display_ascii_filename() {
+        let disposition = ContentDisposition::attachment().filename("report \"final\".pdf");
+        assert_eq!(
+            disposition.to_string(),
+            r#"attachment; filename="report \"final\".pdf""#
+        );
+    }
+
+    #[test]
+    fn display_form_data() {
+        let disposition = ContentDisposition::form_data("avatar").filename("me.png");
+        assert_eq!(
+            disposition.to_string(),
+            r#"form-data; name="avatar"; filename="me.png""#
+        );
+    }
+
+    #[test]
+    fn display_unicode_field_name() {
+        let disposition = ContentDisposition::form_data("имя");
+        assert_eq!(disposition.to_string(), r#"form-data; name="имя""#);
+
+        let parsed: ContentDisposition = disposition.to_string().parse().unwrap();
+        assert_eq!(parsed.get_name(), Some("имя"));
+    }
+
+    #[test]
+    fn display_strips_control_characters() {
+        let disposition = ContentDisposition::inline().filename("a\r\nb\u{e9}");
+        assert_eq!(
+            disposition.to_string(),
+            "inline; filename=\"ab_\"; filename*=UTF-8''ab%C3%A9"
+        );
+    }
+
+    #[test]
+    fn parse_strips_control_characters() {
+        let disposition: ContentDisposition =
+            "attachment; filename*=UTF-8''a%0D%0Ab.txt".parse().unwrap();
+        assert_eq!(disposition.get_filename(), Some("ab.txt"));
+    }
+
+    #[test]
+    fn filename_drops_path_and_bidi_characters() {
+        let disposition = ContentDisposition::attachment().filename("reports/2024.pdf");
+        assert_eq!(disposition.get_filename(), Some("2024.pdf"));
+
+        for (header, filename) in [
+            (
+                "attachment; filename*=UTF-8''..%2F..%2F.bashrc",
+                Some(".bashrc"),
+            ),
+            ("attachment; filename=\"../../etc/passwd\"", Some("passwd")),
+            (r#"attachment; filename="C:\\Users\\a.txt""#, Some("a.txt")),
+            (
+                "attachment; filename*=UTF-8''evil%E2%80%AEtxt.exe",
+                Some("eviltxt.exe"),
+            ),
+            ("attachment; filename=\"..\"", None),
+        ] {
+            let disposition: ContentDisposition = header.parse().unwrap();
+            assert_eq!(disposition.get_filename(), filename, "{header}");
+        }
+    }
+
+    #[test]
+    fn parse_prefers_extended_filename() {
+        let disposition: ContentDisposition =
+            "Attachment; filename*=UTF-8''%E2%82%AC%20rates.txt; filename=\"EUR rates.txt\""
+                .parse()
+                .unwrap();
+        assert_eq!(disposition.get_kind(), DispositionType::Attachment);
+        assert_eq!(disposition.get_filename(), Some("€ rates.txt"));
+    }
+
+    #[test]
+    fn parse_latin1_and_token_values() {
+        let disposition: ContentDisposition = "inline; name=field; filename*=iso-8859-1'en'%A3.txt"
+            .parse()
+            .unwrap();
+        assert_eq!(disposition.get_kind(), DispositionType::Inline);
+        assert_eq!(disposition.get_name(), Some("field"));
+        assert_eq!(disposition.get_filename(), Some("£.txt"));
+    }
+
+    #[test]
+    fn parse_falls_back_to_plain_filename() {
+        for header in [
+            "attachment; filename=\"a.txt\"; filename*=windows-1252''a.txt",
+            "attachment; filename*=UTF-8''%ZZ; filename=\"a.txt\"",
+            "attachment; filename=\"a.txt\"; filename*=UTF-8''%+F",
+        ] {
+            let disposition: ContentDisposition = header.parse().unwrap();
+            assert_eq!(disposition.get_filename(), Some("a.txt"), "{header}");
+        }
+    }
+
+    #[test]
+    fn parse_unknown_type_as_attachment() {
+        let disposition: ContentDisposition = "x-custom".parse().unwrap();
+        assert_eq!(disposition, ContentDisposition::attachment());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "".parse::<ContentDisposition>(),
+            Err(ContentDispositionError::InvalidType)
+        );
+        assert_eq!(
+            "attachment; filename".parse::<ContentDisposition>(),
+            Err(ContentDispositionError::MalformedParameter)
+        );
+        assert_eq!(
+            "attachment; junk; filename=\"x.txt\"".parse::<ContentDisposition>(),
+            Err(ContentDispositionError::MalformedParameter)
+        );
+        assert_eq!(
+            "attachment; fi lename=a".parse::<ContentDisposition>(),
+            Err(ContentDispositionError::MalformedParameter)
+        );
+        assert_eq!(
+            "attachment; filename=a; FILENAME=b".parse::<ContentDisposition>(),
+            Err(ContentDispositionError::MalformedParameter)
+        );
+        assert_eq!(
+            "attachment; filename=\"open".parse::<ContentDisposition>(),
+            Err(ContentDispositionError::MalformedParameter)
+        );
+        // Without a plain filename there is nothing to fall back to.
+        assert_eq!(
+            "attachment; filename*=UTF-8''%ZZ".parse::<ContentDisposition>(),
+            Err(ContentDispositionError::InvalidEncoding)
+        );
+        assert_eq!(
+            "attachment; filename*=UTF-8''%+F".parse::<ContentDisposition>(),
+            Err(ContentDispositionError::InvalidEncoding)
+        );
+    }
+}
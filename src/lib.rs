@@ -1,3 +1,8 @@
+pub mod clock;
+pub mod header;
+pub mod resolve;
+
+#[must_use]
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
 }
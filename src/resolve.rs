@@ -0,0 +1,248 @@
+//! Hostname resolution.
+//!
+//! A [`Resolver`] turns a host and port into socket addresses. The
+//! [`SystemResolver`] uses the operating system, a [`StaticResolver`] serves
+//! a fixed host map for tests and service meshes, and a [`CachingResolver`]
+//! remembers the answers of another resolver for a fixed time to live.
+
+use std::collections::HashMap;
+use std::io::{self, ErrorKind};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::clock::{Clock, SystemClock};
+
+/// A source of socket addresses for hostnames.
+pub trait Resolver: Send + Sync {
+    /// Resolve `host` to the addresses to try when connecting to `port`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host cannot be resolved.
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>>;
+}
+
+/// Resolves hostnames through the operating system.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        Ok((host, port).to_socket_addrs()?.collect())
+    }
+}
+
+/// Resolves hostnames from a fixed map.
+///
+/// ```
+/// use habanero::resolve::{Resolver, StaticResolver};
+///
+/// let resolver = StaticResolver::new().host("api.internal", [10, 0, 0, 7].into());
+/// let addrs = resolver.resolve("api.internal", 8080).unwrap();
+/// assert_eq!(addrs, ["10.0.0.7:8080".parse().unwrap()]);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct StaticResolver {
+    hosts: HashMap<String, Vec<IpAddr>>,
+}
+
+impl StaticResolver {
+    /// Create an empty static resolver.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an address for `host`. Hosts may be given several addresses.
+    #[must_use]
+    pub fn host(mut self, host: &str, addr: IpAddr) -> Self {
+        self.hosts
+            .entry(host.to_ascii_lowercase())
+            .or_default()
+            .push(addr);
+        self
+    }
+}
+
+impl Resolver for StaticResolver {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        self.hosts
+            .get(&host.to_ascii_lowercase())
+            .map(|addrs| addrs.iter().map(|ip| SocketAddr::new(*ip, port)).collect())
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, format!("unknown host {host}")))
+    }
+}
+
+/// Cached answers and when they expire, keyed by lowercase host and port.
+/// An expiry of `None` means the answer never expires.
+type Cache = HashMap<(String, u16), (Option<Instant>, Vec<SocketAddr>)>;
+
+/// Caches the answers of another resolver for a fixed time to live.
+///
+/// Failed lookups are not cached, and expired answers are evicted whenever
+/// a new answer is stored.
+#[derive(Debug)]
+pub struct CachingResolver<R, C = SystemClock> {
+    inner: R,
+    ttl: Duration,
+    clock: C,
+    cache: Mutex<Cache>,
+}
+
+impl<R: Resolver> CachingResolver<R> {
+    /// Cache the answers of `inner` for `ttl`. A `ttl` too large to add to
+    /// the current time, such as [`Duration::MAX`], caches them forever.
+    #[must_use]
+    pub fn new(inner: R, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            clock: SystemClock,
+            cache: Mutex::default(),
+        }
+    }
+}
+
+impl<R: Resolver, C: Clock> CachingResolver<R, C> {
+    /// Use `clock` to decide when cached answers expire.
+    #[must_use]
+    pub fn with_clock<T: Clock>(self, clock: T) -> CachingResolver<R, T> {
+        CachingResolver {
+            inner: self.inner,
+            ttl: self.ttl,
+            clock,
+            cache: self.cache,
+        }
+    }
+
+    /// Forget all cached answers.
+    pub fn clear(&self) {
+        self.cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+impl<R: Resolver, C: Clock> Resolver for CachingResolver<R, C> {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        let key = (host.to_ascii_lowercase(), port);
+        let now = self.clock.now();
+
+        if let Some((expires, addrs)) = self
+            .cache
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&key)
+            && is_fresh(*expires, now)
+        {
+            return Ok(addrs.clone());
+        }
+
+        // Resolve without holding the lock so slow lookups don't block hits.
+        let addrs = self.inner.resolve(host, port)?;
+        let mut cache = self.cache.lock().unwrap_or_else(PoisonError::into_inner);
+        cache.retain(|_, (expires, _)| is_fresh(*expires, now));
+        cache.insert(key, (now.checked_add(self.ttl), addrs.clone()));
+        Ok(addrs)
+    }
+}
+
+fn is_fresh(expires: Option<Instant>, now: Instant) -> bool {
+    expires.is_none_or(|expires| now < expires)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::TestClock;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Counting(AtomicUsize);
+
+    impl Resolver for Counting {
+        fn resolve(&self, _host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![SocketAddr::from(([127, 0, 0, 1], port))])
+        }
+    }
+
+    #[test]
+    fn static_resolver_is_case_insensitive() {
+        let resolver = StaticResolver::new()
+            .host("Example.com", [192, 0, 2, 1].into())
+            .host("example.com", [192, 0, 2, 2].into());
+        let addrs = resolver.resolve("EXAMPLE.com", 80).unwrap();
+        assert_eq!(
+            addrs,
+            [
+                SocketAddr::from(([192, 0, 2, 1], 80)),
+                SocketAddr::from(([192, 0, 2, 2], 80)),
+            ]
+        );
+    }
+
+    #[test]
+    fn static_resolver_unknown_host() {
+        let error = StaticResolver::new().resolve("nowhere", 80).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn system_resolver_resolves_ip_literals() {
+        let addrs = SystemResolver.resolve("127.0.0.1", 8080).unwrap();
+        assert_eq!(addrs, [SocketAddr::from(([127, 0, 0, 1], 8080))]);
+    }
+
+    #[test]
+    fn caching_resolver_expires_after_ttl() {
+        let clock = TestClock::new();
+        let resolver = CachingResolver::new(Counting::default(), Duration::from_secs(30))
+            .with_clock(clock.clone());
+
+        resolver.resolve("example.com", 80).unwrap();
+        resolver.resolve("example.com", 80).unwrap();
+        assert_eq!(resolver.inner.0.load(Ordering::SeqCst), 1);
+
+        resolver.resolve("example.com", 443).unwrap();
+        assert_eq!(resolver.inner.0.load(Ordering::SeqCst), 2);
+
+        clock.advance(Duration::from_secs(30));
+        resolver.resolve("example.com", 80).unwrap();
+        assert_eq!(resolver.inner.0.load(Ordering::SeqCst), 3);
+
+        resolver.clear();
+        resolver.resolve("example.com", 80).unwrap();
+        assert_eq!(resolver.inner.0.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn caching_resolver_huge_ttl_never_expires() {
+        let clock = TestClock::new();
+        let resolver =
+            CachingResolver::new(Counting::default(), Duration::MAX).with_clock(clock.clone());
+
+        resolver.resolve("example.com", 80).unwrap();
+        clock.advance(Duration::from_hours(24 * 365));
+        resolver.resolve("example.com", 80).unwrap();
+        assert_eq!(resolver.inner.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn caching_resolver_evicts_expired_entries() {
+        let clock = TestClock::new();
+        let resolver = CachingResolver::new(Counting::default(), Duration::from_secs(30))
+            .with_clock(clock.clone());
+
+        resolver.resolve("a.example", 80).unwrap();
+        resolver.resolve("b.example", 80).unwrap();
+        clock.advance(Duration::from_secs(30));
+        resolver.resolve("c.example", 80).unwrap();
+
+        let cache = resolver.cache.lock().unwrap();
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains_key(&("c.example".to_string(), 80)));
+    }
+}